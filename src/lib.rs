@@ -0,0 +1,861 @@
+use std::net::{TcpListener, TcpStream};
+use std::rc::Rc;
+use std::sync::Arc;
+use std::time::Duration;
+use std::{
+    fmt::Display,
+    io::{Read, Write},
+    str::FromStr,
+    thread, vec,
+};
+
+use anyhow::Result;
+use flate2::write::DeflateEncoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use httpdate::{fmt_http_date, parse_http_date};
+use strum_macros::{Display, EnumString};
+
+mod router;
+pub use router::{default_router, Next, PathParams, Router};
+
+const CR_LF: &str = "\r\n";
+const MAX_HEADER_SIZE: usize = 8 * 1024;
+const READ_CHUNK_SIZE: usize = 1024;
+const KEEP_ALIVE_TIMEOUT: Duration = Duration::from_secs(5);
+const DEFAULT_DIRECTORY: &str = "/tmp/";
+
+pub const SERVER_ADDRESS: &str = "127.0.0.1:4221";
+
+#[derive(EnumString, Display, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatusCode {
+    #[strum(serialize = "OK")]
+    Ok = 200,
+    #[strum(serialize = "Created")]
+    Created = 201,
+    #[strum(serialize = "Bad Request")]
+    BadRequest = 400,
+    #[strum(serialize = "Partial Content")]
+    PartialContent = 206,
+    #[strum(serialize = "Not Modified")]
+    NotModified = 304,
+    #[strum(serialize = "Forbidden")]
+    Forbidden = 403,
+    #[strum(serialize = "Not Found")]
+    NotFound = 404,
+    #[strum(serialize = "Payload Too Large")]
+    PayloadTooLarge = 413,
+    #[strum(serialize = "Range Not Satisfiable")]
+    RangeNotSatisfiable = 416,
+    #[strum(serialize = "Internal Server Error")]
+    InternalServerError = 500,
+}
+
+#[derive(EnumString, Display, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SupprotedHeader {
+    #[strum(serialize = "Content-Type")]
+    ContentType,
+    #[strum(serialize = "Content-Length")]
+    ContentLength,
+    #[strum(serialize = "Content-Encoding")]
+    ContentEncoding,
+    #[strum(serialize = "User-Agent")]
+    UserAgent,
+    #[strum(serialize = "Accept-Encoding")]
+    AcceptEncoding,
+    #[strum(serialize = "Connection")]
+    Connection,
+    #[strum(serialize = "Expect")]
+    Expect,
+    #[strum(serialize = "Range")]
+    Range,
+    #[strum(serialize = "Accept-Ranges")]
+    AcceptRanges,
+    #[strum(serialize = "Content-Range")]
+    ContentRange,
+    #[strum(serialize = "ETag")]
+    ETag,
+    #[strum(serialize = "If-None-Match")]
+    IfNoneMatch,
+    #[strum(serialize = "If-Modified-Since")]
+    IfModifiedSince,
+    #[strum(serialize = "Last-Modified")]
+    LastModified,
+}
+
+impl SupprotedHeader {
+    /// Whether `header_name` refers to this known header. Header names are
+    /// case-insensitive per RFC 7230 §3.2.
+    fn matches(&self, header_name: &str) -> bool {
+        header_name.eq_ignore_ascii_case(&self.to_string())
+    }
+}
+
+#[derive(EnumString, Display, Debug, Clone, Copy, PartialEq, Eq)]
+enum ContentType {
+    #[strum(serialize = "text/plain")]
+    TextPlain,
+    #[strum(serialize = "application/json")]
+    ApplicationJson,
+    #[strum(serialize = "application/octet-stream")]
+    ApplicationOctetStream,
+    #[strum(serialize = "text/html")]
+    TextHtml,
+}
+
+#[derive(EnumString, Display, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SupportedEncoding {
+    #[strum(serialize = "gzip")]
+    Gzip,
+    #[strum(serialize = "deflate")]
+    Deflate,
+}
+
+impl SupportedEncoding {
+    /// Parses an `Accept-Encoding` header value into `(coding, q)` pairs per
+    /// RFC 7231 §5.3.4: `q` defaults to 1.0 when no `;q=` parameter is given.
+    fn parse_accept_encoding(value: &str) -> Vec<(String, f32)> {
+        value
+            .split(',')
+            .filter_map(|entry| {
+                let mut parts = entry.split(';');
+                let coding = parts.next()?.trim().to_lowercase();
+                if coding.is_empty() {
+                    return None;
+                }
+                let q = parts
+                    .find_map(|param| {
+                        param
+                            .trim()
+                            .strip_prefix("q=")
+                            .and_then(|v| v.trim().parse::<f32>().ok())
+                    })
+                    .unwrap_or(1.0);
+                Some((coding, q))
+            })
+            .collect()
+    }
+
+    /// Picks the highest-`q` coding the server supports out of the request's
+    /// `Accept-Encoding` header, honouring `q=0` refusals and the `*`
+    /// wildcard. Returns `None` when nothing matches, meaning identity.
+    fn select_best(headers: &[Header]) -> Option<SupportedEncoding> {
+        let encoding_header = headers
+            .iter()
+            .find(|h| SupprotedHeader::AcceptEncoding.matches(&h.key))?;
+        let preferences = Self::parse_accept_encoding(encoding_header.value.trim());
+
+        let q_for = |coding: &str| -> f32 {
+            preferences
+                .iter()
+                .find(|(c, _)| c == coding)
+                .or_else(|| preferences.iter().find(|(c, _)| c == "*"))
+                .map(|(_, q)| *q)
+                .unwrap_or(0.0)
+        };
+
+        [SupportedEncoding::Gzip, SupportedEncoding::Deflate]
+            .into_iter()
+            .map(|encoding| (encoding, q_for(&encoding.to_string())))
+            .filter(|(_, q)| *q > 0.0)
+            .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal))
+            .map(|(encoding, _)| encoding)
+    }
+}
+
+#[derive(EnumString, Display, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HttpMethod {
+    #[strum(serialize = "GET")]
+    Get,
+    #[strum(serialize = "POST")]
+    Post,
+    #[strum(serialize = "PUT")]
+    Put,
+    #[strum(serialize = "DELETE")]
+    Delete,
+    #[strum(serialize = "PATCH")]
+    Patch,
+    #[strum(serialize = "HEAD")]
+    Head,
+    #[strum(serialize = "OPTIONS")]
+    Options,
+    #[strum(serialize = "CONNECT")]
+    Connect,
+    #[strum(serialize = "TRACE")]
+    Trace,
+}
+
+#[derive(EnumString, Display, Debug, Clone, Copy, PartialEq, Eq)]
+enum HttpVersion {
+    #[strum(serialize = "HTTP/1.0")]
+    Http1_0,
+    #[strum(serialize = "HTTP/1.1")]
+    Http1_1,
+    #[strum(serialize = "HTTP/2.0")]
+    Http2_0,
+}
+
+/// A single header, keyed by its name as it appeared on the wire. `key` is
+/// a plain string rather than `SupprotedHeader` so headers the server
+/// doesn't have a variant for (`Authorization`, `X-Forwarded-For`, ...) and
+/// repeated headers aren't silently dropped; `SupprotedHeader` remains a
+/// convenience lookup for the headers the server actually interprets.
+#[derive(Clone)]
+pub struct Header {
+    key: String,
+    value: Rc<String>,
+}
+
+impl Header {
+    pub fn new(key: impl ToString, value: Rc<String>) -> Header {
+        Header {
+            key: key.to_string(),
+            value,
+        }
+    }
+
+    /// Keeps every header, known or not, including duplicates — callers that
+    /// care about a specific header look it up case-insensitively themselves.
+    fn parse_headers(headers: Vec<(&str, &str)>) -> Vec<Header> {
+        headers
+            .into_iter()
+            .map(|(key, value)| Header::new(key, Rc::new(value.to_string())))
+            .collect::<Vec<Header>>()
+    }
+}
+
+pub struct HttpRequest {
+    pub method: HttpMethod,
+    pub path: String,
+    version: HttpVersion,
+    headers: Vec<Header>,
+    pub body: Vec<u8>,
+}
+
+impl HttpRequest {
+    /// Returns the first value of a known header, matched case-insensitively.
+    pub fn get_header_value(&self, key: SupprotedHeader) -> Option<&str> {
+        self.get_header_value_by_name(&key.to_string())
+    }
+
+    /// Returns the first value of a header named `name`, matched
+    /// case-insensitively. Works for headers outside `SupprotedHeader` too.
+    pub fn get_header_value_by_name(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|header| header.key.eq_ignore_ascii_case(name))
+            .map(|header| header.value.as_str())
+    }
+
+    /// Returns every value for headers named `name` (case-insensitive), in
+    /// wire order — for repeated headers like `Accept-Encoding`/`Cookie`.
+    pub fn get_header_values(&self, name: &str) -> Vec<&str> {
+        self.headers
+            .iter()
+            .filter(|header| header.key.eq_ignore_ascii_case(name))
+            .map(|header| header.value.as_str())
+            .collect()
+    }
+
+    /// Whether the connection should stay open for another request after this
+    /// one, per HTTP/1.1 semantics: an explicit `Connection` header wins,
+    /// otherwise HTTP/1.1 defaults to keep-alive and HTTP/1.0 defaults to close.
+    fn wants_keep_alive(&self) -> bool {
+        match self.get_header_value(SupprotedHeader::Connection) {
+            Some(value) => !value.trim().eq_ignore_ascii_case("close"),
+            None => self.version == HttpVersion::Http1_1,
+        }
+    }
+
+    /// Parses an `HttpRequest` out of `buffer`, whose first `header_end` bytes
+    /// are the complete status line and headers (as found by `read_request`);
+    /// anything past that offset is treated as the body. The path is
+    /// percent-decoded up front so every handler sees the same decoded text.
+    fn new(buffer: &[u8], header_end: usize) -> Result<HttpRequest> {
+        let mut raw_headers = [httparse::EMPTY_HEADER; 64];
+        let mut parsed = httparse::Request::new(&mut raw_headers);
+        parsed
+            .parse(buffer)
+            .map_err(|e| anyhow::anyhow!("malformed request: {e}"))?;
+
+        let method = HttpMethod::from_str(
+            parsed
+                .method
+                .ok_or_else(|| anyhow::anyhow!("missing method"))?,
+        )?;
+
+        let raw_path = parsed
+            .path
+            .ok_or_else(|| anyhow::anyhow!("missing path"))?;
+        let path = percent_decode(raw_path)
+            .ok_or_else(|| anyhow::anyhow!("invalid percent-encoding in path"))?;
+
+        let version = match parsed.version {
+            Some(0) => HttpVersion::Http1_0,
+            Some(1) => HttpVersion::Http1_1,
+            _ => return Err(anyhow::anyhow!("unsupported HTTP version")),
+        };
+
+        let header_pairs = parsed
+            .headers
+            .iter()
+            .map(|header| {
+                std::str::from_utf8(header.value)
+                    .map(|value| (header.name, value))
+                    .map_err(|e| anyhow::anyhow!("invalid header value: {e}"))
+            })
+            .collect::<Result<Vec<(&str, &str)>>>()?;
+
+        let headers = Header::parse_headers(header_pairs);
+        let body = buffer[header_end..].to_vec();
+
+        Ok(HttpRequest {
+            method,
+            path,
+            version,
+            headers,
+            body,
+        })
+    }
+}
+
+pub struct HttpResponse {
+    status_code: StatusCode,
+    headers: Vec<Header>,
+    body: Vec<u8>,
+    encoding_type: Option<SupportedEncoding>,
+}
+
+impl HttpResponse {
+    pub fn new(
+        status_code: StatusCode,
+        headers: Vec<Header>,
+        body: impl Into<Vec<u8>>,
+        encoding_type: Option<SupportedEncoding>,
+    ) -> HttpResponse {
+        HttpResponse {
+            status_code,
+            headers,
+            body: body.into(),
+            encoding_type,
+        }
+    }
+
+    fn compress_string(self, encoding_type: SupportedEncoding) -> Vec<u8> {
+        match encoding_type {
+            SupportedEncoding::Gzip => {
+                let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+                encoder.write_all(&self.body).unwrap();
+                encoder.finish().unwrap()
+            }
+            SupportedEncoding::Deflate => {
+                let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+                encoder.write_all(&self.body).unwrap();
+                encoder.finish().unwrap()
+            }
+        }
+    }
+
+    fn create_content_length_header<T: AsRef<[u8]>>(body: T) -> Header {
+        Header::new(
+            SupprotedHeader::ContentLength,
+            Rc::new(body.as_ref().len().to_string()),
+        )
+    }
+
+    fn body_to_bytes(self) -> Vec<u8> {
+        let status_line = format!(
+            "{} {} {}",
+            HttpVersion::Http1_1,
+            self.status_code as u16,
+            StatusCode::to_string(&self.status_code)
+        );
+
+        let mut headers = self.headers.clone();
+
+        if !self
+            .headers
+            .iter()
+            .any(|header| SupprotedHeader::ContentType.matches(&header.key))
+        {
+            headers.push(Header::new(
+                SupprotedHeader::ContentType,
+                Rc::new(ContentType::TextPlain.to_string()),
+            ));
+        }
+
+        let body = if let Some(encoding_type) = self.encoding_type {
+            headers.push(Header::new(
+                SupprotedHeader::ContentEncoding,
+                Rc::new(encoding_type.to_string()),
+            ));
+            self.compress_string(encoding_type)
+        } else {
+            self.body.clone()
+        };
+        headers.push(HttpResponse::create_content_length_header(&body));
+
+        let formatted_headers = headers
+            .iter()
+            .map(|header| format!("{}: {}", header.key, header.value))
+            .collect::<Vec<String>>()
+            .join(CR_LF)
+            + CR_LF;
+
+        let response = format!("{}{}{}{}", status_line, CR_LF, formatted_headers, CR_LF);
+        let mut response_bytes = response.into_bytes();
+        response_bytes.extend_from_slice(&body);
+        response_bytes
+    }
+}
+
+impl Display for HttpResponse {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let status_line = format!(
+            "{} {} {}",
+            HttpVersion::Http1_1,
+            self.status_code as u16,
+            StatusCode::to_string(&self.status_code)
+        );
+
+        let headers = self
+            .headers
+            .iter()
+            .map(|header| format!("{}: {}", header.key, header.value))
+            .collect::<Vec<String>>()
+            .join(CR_LF);
+
+        write!(f, "{}{}{}{}", status_line, CR_LF, headers, CR_LF)
+    }
+}
+
+/// Why a full request could not be read off the stream.
+enum RequestReadError {
+    /// The peer closed the connection before sending a complete request.
+    ConnectionClosed,
+    /// The header section exceeded `MAX_HEADER_SIZE` without terminating.
+    HeadersTooLarge,
+    /// The socket read failed, or the bytes received so far don't parse as HTTP.
+    Io(std::io::Error),
+}
+
+/// Reads a full HTTP request off `stream`, starting from whatever bytes of
+/// the next request `leftover` already pipelined in from the previous read:
+/// headers are accumulated until `httparse` reports `Status::Complete`, then
+/// exactly `Content-Length` more bytes are read for the body (defaulting to
+/// none when the header is absent). Returns the request's own bytes together
+/// with the offset where its body starts (so callers can hand the same
+/// buffer to `HttpRequest::new`), plus any bytes read past the end of this
+/// request — the start of a pipelined next request — for the next call.
+fn read_request(
+    stream: &mut TcpStream,
+    leftover: Vec<u8>,
+) -> std::result::Result<(Vec<u8>, usize, Vec<u8>), RequestReadError> {
+    let mut buffer = leftover;
+    let mut chunk = [0u8; READ_CHUNK_SIZE];
+
+    let header_end = loop {
+        let mut raw_headers = [httparse::EMPTY_HEADER; 64];
+        let mut request = httparse::Request::new(&mut raw_headers);
+
+        match request.parse(&buffer) {
+            Ok(httparse::Status::Complete(offset)) => break offset,
+            Ok(httparse::Status::Partial) => {
+                if buffer.len() >= MAX_HEADER_SIZE {
+                    return Err(RequestReadError::HeadersTooLarge);
+                }
+                let bytes_read = stream.read(&mut chunk).map_err(RequestReadError::Io)?;
+                if bytes_read == 0 {
+                    return Err(RequestReadError::ConnectionClosed);
+                }
+                buffer.extend_from_slice(&chunk[..bytes_read]);
+            }
+            Err(e) => {
+                return Err(RequestReadError::Io(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    e.to_string(),
+                )))
+            }
+        }
+    };
+
+    let (content_length, expects_continue) = {
+        let mut raw_headers = [httparse::EMPTY_HEADER; 64];
+        let mut request = httparse::Request::new(&mut raw_headers);
+        // The buffer already parses successfully up to `header_end`, so this can't fail.
+        let _ = request.parse(&buffer);
+
+        let content_length = request
+            .headers
+            .iter()
+            .find(|h| h.name.eq_ignore_ascii_case("content-length"))
+            .and_then(|h| std::str::from_utf8(h.value).ok())
+            .and_then(|v| v.trim().parse::<usize>().ok())
+            .unwrap_or(0);
+
+        // HTTP/1.0 clients don't understand 100-continue; only act on it for 1.1.
+        let expects_continue = request.version == Some(1)
+            && request.headers.iter().any(|h| {
+                h.name.eq_ignore_ascii_case("expect")
+                    && std::str::from_utf8(h.value)
+                        .map(|v| v.trim().eq_ignore_ascii_case("100-continue"))
+                        .unwrap_or(false)
+            });
+
+        (content_length, expects_continue)
+    };
+
+    // Tell the client it's safe to stream the body before we actually read
+    // it, so large uploads (e.g. to POST /files/) aren't sent speculatively.
+    if expects_continue {
+        stream
+            .write_all(format!("{} 100 Continue{CR_LF}{CR_LF}", HttpVersion::Http1_1).as_bytes())
+            .map_err(RequestReadError::Io)?;
+    }
+
+    let total_len = header_end + content_length;
+    while buffer.len() < total_len {
+        let bytes_read = stream.read(&mut chunk).map_err(RequestReadError::Io)?;
+        if bytes_read == 0 {
+            return Err(RequestReadError::ConnectionClosed);
+        }
+        buffer.extend_from_slice(&chunk[..bytes_read]);
+    }
+    let remainder = buffer.split_off(total_len);
+
+    Ok((buffer, header_end, remainder))
+}
+
+/// Decodes `%XX` percent escapes in a request path into raw bytes and
+/// re-validates the result as UTF-8. Returns `None` on a truncated/non-hex
+/// escape or on invalid UTF-8 after decoding, so callers can reject the
+/// request with `400` instead of routing on a mangled path.
+fn percent_decode(path: &str) -> Option<String> {
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' => {
+                let hex = path.get(i + 1..i + 3)?;
+                if !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+                    return None;
+                }
+                decoded.push(u8::from_str_radix(hex, 16).ok()?);
+                i += 3;
+            }
+            b => {
+                decoded.push(b);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8(decoded).ok()
+}
+
+/// Resolves `requested` (the already percent-decoded tail of a `/files/`
+/// path) against `directory`, refusing to leave it. Used by both the GET and
+/// POST `/files/` handlers so a request like `/files/../../etc/passwd` can
+/// never escape the served directory.
+fn resolve_file_path(directory: &str, requested: &str) -> Option<std::path::PathBuf> {
+    if requested.split('/').any(|segment| segment == "..") {
+        return None;
+    }
+
+    let base = std::path::Path::new(directory);
+    let candidate = base.join(requested.trim_start_matches('/'));
+    let canonical_base = base.canonicalize().ok()?;
+
+    match candidate.canonicalize() {
+        // The file exists: make sure canonicalization (which also resolves
+        // symlinks) didn't land us outside the served directory.
+        Ok(canonical_candidate) if canonical_candidate.starts_with(&canonical_base) => {
+            Some(canonical_candidate)
+        }
+        Ok(_) => None,
+        // The file doesn't exist yet (e.g. a POST creating it): only the
+        // parent directory needs to resolve inside the base.
+        Err(_) => {
+            let parent = candidate.parent()?.canonicalize().ok()?;
+            parent.starts_with(&canonical_base).then_some(candidate)
+        }
+    }
+}
+
+/// Outcome of evaluating a `Range` header against a file's total length.
+enum RangeResult {
+    /// No range requested, or the header was syntactically invalid and
+    /// should be ignored in favour of a full response (per RFC 7233 §3.1).
+    None,
+    /// `(start, end)`, both inclusive byte offsets within the file.
+    Satisfiable(usize, usize),
+    /// The range fell entirely outside the file, or its end precedes its start.
+    Unsatisfiable,
+}
+
+/// Parses a `Range: bytes=...` header value against a file of `total_len`
+/// bytes. Only a single byte-range-spec is supported (`start-end`, `start-`,
+/// or `-suffix_len`); multi-range requests are treated as absent rather than
+/// rejected, matching how most static file servers degrade.
+fn evaluate_range(value: &str, total_len: usize) -> RangeResult {
+    let Some(spec) = value.trim().strip_prefix("bytes=") else {
+        return RangeResult::None;
+    };
+    if spec.contains(',') {
+        return RangeResult::None;
+    }
+    let Some((start_str, end_str)) = spec.split_once('-') else {
+        return RangeResult::None;
+    };
+
+    if total_len == 0 {
+        return RangeResult::Unsatisfiable;
+    }
+    let last = total_len - 1;
+
+    let range = match (start_str.trim(), end_str.trim()) {
+        ("", "") => None,
+        ("", suffix) => suffix
+            .parse::<usize>()
+            .ok()
+            .filter(|&n| n > 0)
+            .map(|n| (last.saturating_sub(n - 1), last)),
+        (start, "") => start.parse::<usize>().ok().map(|s| (s, last)),
+        (start, end) => match (start.parse::<usize>(), end.parse::<usize>()) {
+            (Ok(s), Ok(e)) => Some((s, e.min(last))),
+            _ => None,
+        },
+    };
+
+    match range {
+        Some((start, end)) if start <= end && start <= last => RangeResult::Satisfiable(start, end),
+        Some(_) => RangeResult::Unsatisfiable,
+        None => RangeResult::None,
+    }
+}
+
+/// Serves `file_path` like a real static-file server: conditional requests
+/// (`If-None-Match` takes precedence over `If-Modified-Since`) short-circuit
+/// to `304`, and a satisfiable `Range` header yields `206` with the matching
+/// slice; an out-of-bounds range yields `416`.
+fn serve_file(
+    request: &HttpRequest,
+    file_path: std::path::PathBuf,
+    mut response_headers: Vec<Header>,
+    content_encoding: Option<SupportedEncoding>,
+) -> HttpResponse {
+    let metadata = match std::fs::metadata(&file_path) {
+        Ok(metadata) => metadata,
+        Err(_) => {
+            return HttpResponse::new(
+                StatusCode::NotFound,
+                response_headers,
+                StatusCode::NotFound.to_string(),
+                content_encoding,
+            )
+        }
+    };
+
+    let total_len = metadata.len() as usize;
+    let modified = metadata
+        .modified()
+        .unwrap_or(std::time::SystemTime::UNIX_EPOCH);
+    let modified_secs = modified
+        .duration_since(std::time::SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let etag = format!("\"{:x}-{:x}\"", total_len, modified_secs);
+
+    response_headers.push(Header::new(
+        SupprotedHeader::AcceptRanges,
+        Rc::new("bytes".to_string()),
+    ));
+    response_headers.push(Header::new(SupprotedHeader::ETag, Rc::new(etag.clone())));
+    response_headers.push(Header::new(
+        SupprotedHeader::LastModified,
+        Rc::new(fmt_http_date(modified)),
+    ));
+
+    let not_modified = if let Some(if_none_match) =
+        request.get_header_value(SupprotedHeader::IfNoneMatch)
+    {
+        if_none_match
+            .split(',')
+            .any(|candidate| candidate.trim() == etag || candidate.trim() == "*")
+    } else if let Some(if_modified_since) =
+        request.get_header_value(SupprotedHeader::IfModifiedSince)
+    {
+        parse_http_date(if_modified_since.trim())
+            .map(|since| {
+                let since_secs = since
+                    .duration_since(std::time::SystemTime::UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_secs();
+                modified_secs <= since_secs
+            })
+            .unwrap_or(false)
+    } else {
+        false
+    };
+
+    if not_modified {
+        return HttpResponse::new(StatusCode::NotModified, response_headers, String::new(), None);
+    }
+
+    let content = match std::fs::read(&file_path) {
+        Ok(bytes) => bytes,
+        Err(_) => {
+            return HttpResponse::new(
+                StatusCode::InternalServerError,
+                response_headers,
+                StatusCode::InternalServerError.to_string(),
+                content_encoding,
+            )
+        }
+    };
+
+    response_headers.push(Header::new(
+        SupprotedHeader::ContentType,
+        Rc::new(ContentType::ApplicationOctetStream.to_string()),
+    ));
+
+    let range = request
+        .get_header_value(SupprotedHeader::Range)
+        .map(|value| evaluate_range(value, total_len))
+        .unwrap_or(RangeResult::None);
+
+    match range {
+        RangeResult::Satisfiable(start, end) => {
+            response_headers.push(Header::new(
+                SupprotedHeader::ContentRange,
+                Rc::new(format!("bytes {start}-{end}/{total_len}")),
+            ));
+            let slice = content[start..=end].to_vec();
+            HttpResponse::new(
+                StatusCode::PartialContent,
+                response_headers,
+                slice,
+                content_encoding,
+            )
+        }
+        RangeResult::Unsatisfiable => {
+            response_headers.push(Header::new(
+                SupprotedHeader::ContentRange,
+                Rc::new(format!("bytes */{total_len}")),
+            ));
+            HttpResponse::new(
+                StatusCode::RangeNotSatisfiable,
+                response_headers,
+                String::new(),
+                None,
+            )
+        }
+        RangeResult::None => HttpResponse::new(
+            StatusCode::Ok,
+            response_headers,
+            content,
+            content_encoding,
+        ),
+    }
+}
+
+/// Appends the `Connection` header reflecting `keep_alive`, writes the
+/// response to `stream`, and returns `keep_alive` so callers can use the
+/// result directly as their own return value — or `false` regardless of
+/// `keep_alive` if the peer went away mid-write, so a client that hangs up
+/// doesn't panic the connection's thread.
+fn send_response(stream: &mut TcpStream, mut response: HttpResponse, keep_alive: bool) -> bool {
+    response.headers.push(Header::new(
+        SupprotedHeader::Connection,
+        Rc::new(if keep_alive { "keep-alive" } else { "close" }.to_string()),
+    ));
+
+    let bytes = response.body_to_bytes();
+    if stream.write_all(&bytes).is_err() || stream.flush().is_err() {
+        return false;
+    }
+
+    keep_alive
+}
+
+fn handle_client(mut stream: TcpStream, router: Arc<Router>) {
+    if stream.set_read_timeout(Some(KEEP_ALIVE_TIMEOUT)).is_err() {
+        return;
+    }
+
+    let mut leftover = Vec::new();
+    loop {
+        match handle_one_request(&mut stream, &router, leftover) {
+            Some(next_leftover) => leftover = next_leftover,
+            None => return,
+        }
+    }
+}
+
+/// Services a single request off `stream`, starting from `leftover` bytes
+/// already read past the end of the previous request (empty on the first
+/// call). Returns the bytes of whatever came after this request — to carry
+/// into the next call — if the connection should stay open, or `None` once
+/// it should close.
+fn handle_one_request(stream: &mut TcpStream, router: &Router, leftover: Vec<u8>) -> Option<Vec<u8>> {
+    let (buffer, header_end, remainder) = match read_request(stream, leftover) {
+        Ok(parts) => parts,
+        Err(RequestReadError::ConnectionClosed) => return None,
+        Err(RequestReadError::HeadersTooLarge) => {
+            let response = HttpResponse::new(
+                StatusCode::PayloadTooLarge,
+                vec![],
+                StatusCode::PayloadTooLarge.to_string(),
+                None,
+            );
+            stream.write_all(&response.body_to_bytes()).unwrap();
+            return None;
+        }
+        Err(RequestReadError::Io(e)) => {
+            if !matches!(
+                e.kind(),
+                std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+            ) {
+                println!("error reading request: {}", e);
+            }
+            return None;
+        }
+    };
+
+    let request = match HttpRequest::new(&buffer, header_end) {
+        Ok(request) => request,
+        Err(e) => {
+            let response = HttpResponse::new(StatusCode::BadRequest, vec![], e.to_string(), None);
+            stream.write_all(&response.body_to_bytes()).unwrap();
+            return None;
+        }
+    };
+
+    let keep_alive = request.wants_keep_alive();
+    let response = router.dispatch(&request);
+
+    send_response(stream, response, keep_alive).then_some(remainder)
+}
+
+/// Starts the server, serving `router` (or the default route set, rooted at
+/// `/tmp/`, when `None`). Blocks forever accepting connections, each
+/// serviced on its own thread.
+pub fn start_server(router: Option<Router>) {
+    let router = Arc::new(router.unwrap_or_else(|| default_router(DEFAULT_DIRECTORY.to_string())));
+
+    let listener = TcpListener::bind(SERVER_ADDRESS).unwrap();
+
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => {
+                let router = Arc::clone(&router);
+                thread::spawn(move || {
+                    handle_client(stream, router);
+                });
+            }
+            Err(e) => {
+                println!("error: {}", e);
+            }
+        }
+    }
+}
@@ -0,0 +1,259 @@
+use crate::{
+    resolve_file_path, serve_file, HttpMethod, HttpRequest, HttpResponse, StatusCode,
+    SupportedEncoding, SupprotedHeader,
+};
+
+type Handler = dyn Fn(&HttpRequest, &PathParams) -> HttpResponse + Send + Sync;
+type MiddlewareFn = dyn Fn(&HttpRequest, &Next) -> HttpResponse + Send + Sync;
+
+struct Route {
+    method: HttpMethod,
+    pattern: String,
+    handler: Box<Handler>,
+}
+
+/// The `:name`/`*` segments a route pattern captured out of a matched
+/// request path, looked up by name — the trailing `*`, if present, is
+/// captured under the name `"*"`.
+pub struct PathParams {
+    captures: Vec<(String, String)>,
+}
+
+impl PathParams {
+    /// Returns the value captured for `name`, or `None` if the matched route
+    /// had no such parameter.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.captures
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Maps `(method, path pattern)` to a handler, running every request through
+/// a chain of middleware first. Patterns are `/`-separated: a `:name`
+/// segment matches any single non-empty path segment, and a trailing `*`
+/// matches the rest of the path (including an empty remainder). Routes and
+/// middleware are registered with the builder methods below before the
+/// server starts; there is no way to add either once `start_server` is
+/// running.
+pub struct Router {
+    routes: Vec<Route>,
+    middlewares: Vec<Box<MiddlewareFn>>,
+}
+
+impl Router {
+    pub fn new() -> Self {
+        Router {
+            routes: Vec::new(),
+            middlewares: Vec::new(),
+        }
+    }
+
+    /// Registers `handler` for requests matching `method` and `pattern`. The
+    /// first matching route, in registration order, wins.
+    pub fn route(
+        mut self,
+        method: HttpMethod,
+        pattern: &str,
+        handler: impl Fn(&HttpRequest, &PathParams) -> HttpResponse + Send + Sync + 'static,
+    ) -> Self {
+        self.routes.push(Route {
+            method,
+            pattern: pattern.to_string(),
+            handler: Box::new(handler),
+        });
+        self
+    }
+
+    /// Wraps every request in `middleware`, outermost-registered-first: the
+    /// first middleware added is the first to see the request and the last
+    /// to see the response.
+    pub fn middleware(
+        mut self,
+        middleware: impl Fn(&HttpRequest, &Next) -> HttpResponse + Send + Sync + 'static,
+    ) -> Self {
+        self.middlewares.push(Box::new(middleware));
+        self
+    }
+
+    /// Runs `request` through the middleware chain down to the matched
+    /// route's handler, or a `404` if nothing matches.
+    pub(crate) fn dispatch(&self, request: &HttpRequest) -> HttpResponse {
+        let matched = self.routes.iter().find_map(|route| {
+            if route.method != request.method {
+                return None;
+            }
+            match_pattern(&route.pattern, &request.path).map(|params| (route, params))
+        });
+
+        let respond = |request: &HttpRequest| match &matched {
+            Some((route, params)) => (route.handler)(request, params),
+            None => HttpResponse::new(
+                StatusCode::NotFound,
+                vec![],
+                StatusCode::NotFound.to_string(),
+                None,
+            ),
+        };
+
+        Next {
+            middlewares: &self.middlewares,
+            handler: &respond,
+        }
+        .run(request)
+    }
+}
+
+impl Default for Router {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The remainder of a middleware chain: calling `run` executes the next
+/// middleware, or the matched route's handler once the chain is exhausted.
+pub struct Next<'a> {
+    middlewares: &'a [Box<MiddlewareFn>],
+    handler: &'a dyn Fn(&HttpRequest) -> HttpResponse,
+}
+
+impl<'a> Next<'a> {
+    pub fn run(&self, request: &HttpRequest) -> HttpResponse {
+        match self.middlewares.split_first() {
+            Some((middleware, rest)) => {
+                let next = Next {
+                    middlewares: rest,
+                    handler: self.handler,
+                };
+                middleware(request, &next)
+            }
+            None => (self.handler)(request),
+        }
+    }
+}
+
+/// Matches `path` against a route `pattern` segment by segment: `:name`
+/// accepts any single non-empty segment and captures it under `name`, a
+/// trailing `*` accepts the rest of the path (including nothing at all) and
+/// captures it under `"*"`, and every other segment must match literally.
+/// Returns the captured parameters on a match, `None` otherwise.
+fn match_pattern(pattern: &str, path: &str) -> Option<PathParams> {
+    let mut captures = Vec::new();
+    let mut pattern_segments = pattern.split('/');
+    let mut path_segments = path.split('/');
+    loop {
+        match (pattern_segments.next(), path_segments.next()) {
+            (Some("*"), first) => {
+                let rest: Vec<&str> = first.into_iter().chain(path_segments).collect();
+                captures.push(("*".to_string(), rest.join("/")));
+                return Some(PathParams { captures });
+            }
+            (Some(p), Some(s)) if p.starts_with(':') => {
+                if s.is_empty() {
+                    return None;
+                }
+                captures.push((p[1..].to_string(), s.to_string()));
+            }
+            (Some(p), Some(s)) if p == s => {}
+            (None, None) => return Some(PathParams { captures }),
+            _ => return None,
+        }
+    }
+}
+
+/// Logs the method and path of every request, and the status line of its
+/// response, replacing the server's former inline `println!`s.
+fn log_request(request: &HttpRequest, next: &Next) -> HttpResponse {
+    println!("{} {}", request.method, request.path);
+    let response = next.run(request);
+    println!("Response: {}", response);
+    response
+}
+
+/// Picks a response encoding from the request's `Accept-Encoding` header
+/// once the handler has produced a response, so individual handlers don't
+/// need to thread encoding negotiation through themselves. Left alone are
+/// `206`/`304`/`416` responses (their body is a range slice, empty, or
+/// absent by definition) and any response whose body is already empty, so a
+/// status that must carry no body never grows a `Content-Encoding` header.
+fn negotiate_encoding(request: &HttpRequest, next: &Next) -> HttpResponse {
+    let mut response = next.run(request);
+    let is_full_response = !matches!(
+        response.status_code,
+        StatusCode::PartialContent | StatusCode::NotModified | StatusCode::RangeNotSatisfiable
+    );
+    if is_full_response && !response.body.is_empty() && response.encoding_type.is_none() {
+        response.encoding_type = SupportedEncoding::select_best(&request.headers);
+    }
+    response
+}
+
+/// Builds the server's built-in routes (`/`, `/echo/:text`, `/user-agent`,
+/// and `GET`/`POST /files/*`, serving out of `directory`), wrapped in
+/// request logging and Accept-Encoding negotiation.
+pub fn default_router(directory: String) -> Router {
+    let get_directory = directory.clone();
+    let post_directory = directory;
+
+    Router::new()
+        .middleware(log_request)
+        .middleware(negotiate_encoding)
+        .route(HttpMethod::Get, "/", |_request, _params| {
+            HttpResponse::new(StatusCode::Ok, vec![], "Hello, World!".to_string(), None)
+        })
+        .route(HttpMethod::Get, "/echo/:text", |_request, params| {
+            let text = params.get("text").unwrap_or_default();
+            HttpResponse::new(StatusCode::Ok, vec![], text.to_string(), None)
+        })
+        .route(HttpMethod::Get, "/user-agent", |request, _params| {
+            match request.get_header_value(SupprotedHeader::UserAgent) {
+                Some(user_agent) => {
+                    HttpResponse::new(StatusCode::Ok, vec![], user_agent.to_string(), None)
+                }
+                None => HttpResponse::new(
+                    StatusCode::BadRequest,
+                    vec![],
+                    StatusCode::BadRequest.to_string(),
+                    None,
+                ),
+            }
+        })
+        .route(HttpMethod::Get, "/files/*", move |request, params| {
+            let tail = params.get("*").unwrap_or_default();
+            match resolve_file_path(&get_directory, tail) {
+                Some(file_path) => serve_file(request, file_path, vec![], None),
+                None => HttpResponse::new(
+                    StatusCode::NotFound,
+                    vec![],
+                    StatusCode::NotFound.to_string(),
+                    None,
+                ),
+            }
+        })
+        .route(HttpMethod::Post, "/files/*", move |request, params| {
+            let tail = params.get("*").unwrap_or_default();
+            match resolve_file_path(&post_directory, tail) {
+                Some(file_path) => match std::fs::write(&file_path, &request.body) {
+                    Ok(_) => HttpResponse::new(
+                        StatusCode::Created,
+                        vec![],
+                        StatusCode::Created.to_string(),
+                        None,
+                    ),
+                    Err(_) => HttpResponse::new(
+                        StatusCode::InternalServerError,
+                        vec![],
+                        StatusCode::InternalServerError.to_string(),
+                        None,
+                    ),
+                },
+                None => HttpResponse::new(
+                    StatusCode::Forbidden,
+                    vec![],
+                    StatusCode::Forbidden.to_string(),
+                    None,
+                ),
+            }
+        })
+}
@@ -1,6 +1,7 @@
 use ::http_server::start_server;
 use ::http_server::SERVER_ADDRESS;
 use reqwest::blocking::Client;
+use std::io::Read;
 use std::thread;
 
 static SERVER_STARTED: OnceCell<()> = OnceCell::new();
@@ -79,6 +80,29 @@ fn test_files() {
     assert_eq!(response.text().unwrap(), "test file");
 }
 
+#[test]
+fn test_accept_encoding_quality() {
+    start_test_server();
+    let client = Client::new();
+    let response = client
+        .get(&format!("http://{}/", SERVER_ADDRESS))
+        .header("Accept-Encoding", "deflate;q=1.0, gzip;q=0.5")
+        .send()
+        .expect("Failed to send request");
+
+    assert!(response.status().is_success());
+    assert_eq!(
+        response.headers().get("content-encoding").unwrap(),
+        "deflate"
+    );
+
+    let body = response.bytes().unwrap();
+    let mut decoder = flate2::read::DeflateDecoder::new(&body[..]);
+    let mut decoded = String::new();
+    decoder.read_to_string(&mut decoded).unwrap();
+    assert_eq!(decoded, "Hello, World!");
+}
+
 #[test]
 fn test_post_files() {
     start_test_server();